@@ -0,0 +1,253 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! TOML configuration-file support for building [`HdfsRegistry`](crate::HdfsRegistry)
+//! connections without hard-coding URLs or auth parameters.
+//!
+//! The first file found, in order, wins:
+//!
+//! 1. the path in the `HDFS_NATIVE_CONFIG` environment variable
+//! 2. `hdfs-native.toml` in the current working directory
+//! 3. `.hdfs-native.toml` in the user's home directory
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ConnectionProperties;
+use crate::HdfsErr;
+
+/// Environment variable that, if set, points directly at a config file.
+pub static CONFIG_ENV_VAR: &str = "HDFS_NATIVE_CONFIG";
+static CWD_CONFIG_FILE: &str = "hdfs-native.toml";
+static HOME_CONFIG_FILE: &str = ".hdfs-native.toml";
+
+/// Deployment-time defaults for connecting to a cluster, loaded from a TOML
+/// file via [`HdfsNativeConfig::load`].
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct HdfsNativeConfig {
+    /// Default NameNode host (e.g. `hdfs://namenode.example.com`), used when
+    /// a URL passed to `HdfsRegistry::get` omits the host entirely. A URL
+    /// that names a host but omits the port is rejected, not defaulted.
+    #[serde(default)]
+    pub namenode_host: Option<String>,
+    /// Default NameNode RPC port.
+    #[serde(default)]
+    pub namenode_port: Option<u16>,
+    /// Default user name to authenticate as, passed to `hdfsBuilderSetUserName`.
+    /// Applies only to URLs that fall back to `namenode_host`.
+    #[serde(default)]
+    pub namenode_user: Option<String>,
+    /// Default Kerberos ticket cache path, passed to `hdfsBuilderSetKerbTicketCachePath`.
+    /// Applies only to URLs that fall back to `namenode_host`.
+    #[serde(default)]
+    pub kerberos_ticket_cache_path: Option<String>,
+    /// Arbitrary builder configuration key/value pairs, passed through
+    /// `hdfsBuilderConfSetStr` before connecting.
+    #[serde(default)]
+    pub builder_conf: HashMap<String, String>,
+}
+
+impl HdfsNativeConfig {
+    /// Locates the config file to load, searching (in order) the
+    /// `HDFS_NATIVE_CONFIG` env var, `./hdfs-native.toml`, and
+    /// `~/.hdfs-native.toml`. Returns `None` if none of them exist.
+    pub fn locate() -> Option<PathBuf> {
+        if let Ok(path) = env::var(CONFIG_ENV_VAR) {
+            let path = PathBuf::from(path);
+            if path.is_file() {
+                return Some(path);
+            }
+        }
+
+        let cwd_path = PathBuf::from(CWD_CONFIG_FILE);
+        if cwd_path.is_file() {
+            return Some(cwd_path);
+        }
+
+        if let Some(home) = dirs_home() {
+            let home_path = home.join(HOME_CONFIG_FILE);
+            if home_path.is_file() {
+                return Some(home_path);
+            }
+        }
+
+        None
+    }
+
+    /// Locates and parses the config file, returning `None` if no config
+    /// file was found anywhere in the search path.
+    pub fn load() -> Result<Option<HdfsNativeConfig>, HdfsErr> {
+        match Self::locate() {
+            Some(path) => Self::load_from(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses the config file at `path`.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<HdfsNativeConfig, HdfsErr> {
+        let contents = fs::read_to_string(path.as_ref()).map_err(|e| {
+            HdfsErr::Generic(format!(
+                "Couldn't read config file {}: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })?;
+
+        toml::from_str(&contents)
+            .map_err(|e| HdfsErr::Generic(format!("Couldn't parse config file: {}", e)))
+    }
+
+    /// Builds the default [`ConnectionProperties`] described by this config.
+    pub fn connection_properties(&self) -> ConnectionProperties {
+        ConnectionProperties {
+            namenode_host: self.namenode_host.clone(),
+            namenode_port: self.namenode_port,
+            namenode_user: self.namenode_user.clone(),
+            kerberos_ticket_cache_path: self.kerberos_ticket_cache_path.clone(),
+        }
+    }
+}
+
+/// Writes a commented sample config file to `path`, for operators to copy
+/// and edit rather than writing one from scratch.
+pub fn write_sample_config<P: AsRef<Path>>(path: P) -> Result<(), HdfsErr> {
+    let sample = r#"# Sample hdfs-native configuration file.
+#
+# Place this at ./hdfs-native.toml, ~/.hdfs-native.toml, or point the
+# HDFS_NATIVE_CONFIG environment variable at it.
+
+# Default NameNode used when a URL passed to HdfsRegistry::get omits the host.
+# namenode_host = "hdfs://namenode.example.com"
+# namenode_port = 8020
+
+# Default identity to connect as, for URLs that use the default NameNode.
+# namenode_user = "hadoop"
+# kerberos_ticket_cache_path = "/tmp/krb5cc_1000"
+
+# Arbitrary libhdfs3 builder configuration, applied via hdfsBuilderConfSetStr.
+# [builder_conf]
+# "input.read.timeout" = "60000"
+# "output.write.timeout" = "60000"
+"#;
+
+    fs::write(path.as_ref(), sample).map_err(|e| {
+        HdfsErr::Generic(format!(
+            "Couldn't write sample config to {}: {}",
+            path.as_ref().display(),
+            e
+        ))
+    })
+}
+
+fn dirs_home() -> Option<PathBuf> {
+    env::var("HOME").ok().map(PathBuf::from)
+}
+
+/// Guards tests (in this module and elsewhere in the crate) that mutate the
+/// process-wide `CONFIG_ENV_VAR` environment variable, so they don't race
+/// each other.
+#[cfg(test)]
+pub(crate) static ENV_VAR_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn unique_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("hdfs-native-test-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_locate_returns_env_var_path() {
+        let _guard = ENV_VAR_LOCK.lock().unwrap();
+        let path = unique_path("env.toml");
+        fs::write(&path, "").unwrap();
+        env::set_var(CONFIG_ENV_VAR, &path);
+
+        assert_eq!(HdfsNativeConfig::locate(), Some(path.clone()));
+
+        env::remove_var(CONFIG_ENV_VAR);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_locate_ignores_env_var_pointing_at_missing_file() {
+        let _guard = ENV_VAR_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV_VAR, unique_path("does-not-exist.toml"));
+
+        assert_ne!(
+            HdfsNativeConfig::locate(),
+            Some(unique_path("does-not-exist.toml"))
+        );
+
+        env::remove_var(CONFIG_ENV_VAR);
+    }
+
+    #[test]
+    fn test_write_sample_config_round_trips_to_all_defaults() {
+        let path = unique_path("sample.toml");
+        write_sample_config(&path).unwrap();
+
+        let config = HdfsNativeConfig::load_from(&path).unwrap();
+
+        assert!(config.namenode_host.is_none());
+        assert!(config.namenode_port.is_none());
+        assert!(config.namenode_user.is_none());
+        assert!(config.kerberos_ticket_cache_path.is_none());
+        assert!(config.builder_conf.is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_from_parses_populated_config() {
+        let path = unique_path("populated.toml");
+        fs::write(
+            &path,
+            r#"
+namenode_host = "hdfs://nn1.example.com"
+namenode_port = 8020
+namenode_user = "hadoop"
+kerberos_ticket_cache_path = "/tmp/krb5cc_1000"
+
+[builder_conf]
+"input.read.timeout" = "60000"
+"#,
+        )
+        .unwrap();
+
+        let config = HdfsNativeConfig::load_from(&path).unwrap();
+
+        assert_eq!(config.namenode_host.as_deref(), Some("hdfs://nn1.example.com"));
+        assert_eq!(config.namenode_port, Some(8020));
+        assert_eq!(config.namenode_user.as_deref(), Some("hadoop"));
+        assert_eq!(
+            config.kerberos_ticket_cache_path.as_deref(),
+            Some("/tmp/krb5cc_1000")
+        );
+        assert_eq!(
+            config.builder_conf.get("input.read.timeout").map(String::as_str),
+            Some("60000")
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,578 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Safe wrappers around the raw libhdfs3 filesystem and file handles.
+
+use std::slice;
+
+use libc::{c_int, c_void};
+
+use crate::err::HdfsErr;
+use crate::raw::*;
+use crate::to_raw;
+use crate::util::HdfsUtil;
+
+const O_RDONLY: c_int = 0;
+const O_WRONLY: c_int = 1;
+const O_APPEND: c_int = 1024;
+
+/// A handle to a connected HDFS (or local `file://`) filesystem.
+///
+/// Cloning an `HdfsFs` is cheap: it copies the underlying native pointer, and
+/// all clones share the same connection.
+#[derive(Debug, Clone)]
+pub struct HdfsFs {
+    pub url: String,
+    raw: hdfsFS,
+}
+
+unsafe impl Send for HdfsFs {}
+unsafe impl Sync for HdfsFs {}
+
+impl HdfsFs {
+    pub(crate) fn new(url: String, raw: hdfsFS) -> HdfsFs {
+        HdfsFs { url, raw }
+    }
+
+    /// Returns the underlying native filesystem handle, for APIs like
+    /// `hdfsCopy`/`hdfsMove` that take two `hdfsFS` handles at once.
+    pub(crate) fn raw_handle(&self) -> hdfsFS {
+        self.raw
+    }
+
+    /// Returns whether `path` exists on this filesystem.
+    pub fn exist(&self, path: &str) -> bool {
+        unsafe { hdfsExists(self.raw, to_raw!(path)) == 0 }
+    }
+
+    /// Creates `path` for writing, truncating it if it already exists.
+    pub fn create(&self, path: &str) -> Result<HdfsFile, HdfsErr> {
+        OpenOptions::new().write(true).open(self, path)
+    }
+
+    /// Opens `path` for reading.
+    pub fn open(&self, path: &str) -> Result<HdfsFile, HdfsErr> {
+        OpenOptions::new().read(true).open(self, path)
+    }
+
+    /// Opens `path` according to `options`. Prefer [`HdfsFs::open`] or
+    /// [`HdfsFs::create`] for the common cases.
+    pub fn open_with(&self, path: &str, options: &OpenOptions) -> Result<HdfsFile, HdfsErr> {
+        let file = unsafe {
+            hdfsOpenFile(
+                self.raw,
+                to_raw!(path),
+                options.flags(),
+                options.buffer_size.unwrap_or(0),
+                options.replication.unwrap_or(0),
+                options.block_size.unwrap_or(0),
+            )
+        };
+
+        if file.is_null() {
+            return Err(HdfsErr::Generic(format!("Couldn't open file {}", path)));
+        }
+
+        Ok(HdfsFile {
+            fs: self.raw,
+            raw: file,
+        })
+    }
+
+    /// Deletes `path`, optionally recursing into directories.
+    pub fn delete(&self, path: &str, recursive: bool) -> Result<bool, HdfsErr> {
+        let ret = unsafe { hdfsDelete(self.raw, to_raw!(path), recursive as c_int) };
+
+        if ret == 0 {
+            Ok(true)
+        } else {
+            Err(HdfsErr::Generic(format!("Couldn't delete {}", path)))
+        }
+    }
+
+    /// Creates `path` and any missing parent directories.
+    pub fn mkdir(&self, path: &str) -> Result<bool, HdfsErr> {
+        let ret = unsafe { hdfsCreateDirectory(self.raw, to_raw!(path)) };
+
+        if ret == 0 {
+            Ok(true)
+        } else {
+            Err(HdfsErr::Generic(format!(
+                "Couldn't create directory {}",
+                path
+            )))
+        }
+    }
+
+    /// Renames `src` to `dst` within this filesystem.
+    pub fn rename(&self, src: &str, dst: &str) -> Result<bool, HdfsErr> {
+        let ret = unsafe { hdfsRename(self.raw, to_raw!(src), to_raw!(dst)) };
+
+        if ret == 0 {
+            Ok(true)
+        } else {
+            Err(HdfsErr::Generic(format!(
+                "Couldn't rename {} to {}",
+                src, dst
+            )))
+        }
+    }
+
+    /// Changes the permission bits of `path` to `mode` (e.g. `0o755`).
+    pub fn chmod(&self, path: &str, mode: i16) -> Result<(), HdfsErr> {
+        let ret = unsafe { hdfsChmod(self.raw, to_raw!(path), mode) };
+
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(HdfsErr::Generic(format!("Couldn't chmod {}", path)))
+        }
+    }
+
+    /// Changes the owner and/or group of `path`. Passing `None` for either
+    /// leaves that attribute unchanged.
+    pub fn chown(&self, path: &str, owner: Option<&str>, group: Option<&str>) -> Result<(), HdfsErr> {
+        let owner_cstr = owner.map(|o| std::ffi::CString::new(o).unwrap());
+        let group_cstr = group.map(|g| std::ffi::CString::new(g).unwrap());
+        let owner_ptr = owner_cstr
+            .as_ref()
+            .map(|c| c.as_ptr())
+            .unwrap_or(std::ptr::null());
+        let group_ptr = group_cstr
+            .as_ref()
+            .map(|c| c.as_ptr())
+            .unwrap_or(std::ptr::null());
+
+        let ret = unsafe { hdfsChown(self.raw, to_raw!(path), owner_ptr, group_ptr) };
+
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(HdfsErr::Generic(format!("Couldn't chown {}", path)))
+        }
+    }
+
+    /// Sets the replication factor of `path`.
+    pub fn set_replication(&self, path: &str, replication: i16) -> Result<(), HdfsErr> {
+        let ret = unsafe { hdfsSetReplication(self.raw, to_raw!(path), replication) };
+
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(HdfsErr::Generic(format!(
+                "Couldn't set replication of {}",
+                path
+            )))
+        }
+    }
+
+    /// Sets the modification and access times of `path`, in seconds since
+    /// the epoch.
+    pub fn set_times(&self, path: &str, mtime: i64, atime: i64) -> Result<(), HdfsErr> {
+        let ret = unsafe { hdfsUtime(self.raw, to_raw!(path), mtime, atime) };
+
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(HdfsErr::Generic(format!("Couldn't set times of {}", path)))
+        }
+    }
+
+    /// Returns the [`FileStatus`] of `path`.
+    pub fn get_file_status(&self, path: &str) -> Result<FileStatus, HdfsErr> {
+        let info = unsafe { hdfsGetPathInfo(self.raw, to_raw!(path)) };
+
+        if info.is_null() {
+            return Err(HdfsErr::Generic(format!("Couldn't stat {}", path)));
+        }
+
+        let status = unsafe { FileStatus::from_raw(&*info) };
+        unsafe { hdfsFreeFileInfo(info, 1) };
+        Ok(status)
+    }
+
+    /// Returns a [`Readdir`] iterator over the entries directly under
+    /// `path`, yielding one [`FileStatus`] at a time. The directory listing
+    /// is fetched in one `hdfsListDirectory` call, and its native memory is
+    /// freed all at once when the iterator is dropped, not entry by entry.
+    /// Prefer this over
+    /// [`HdfsFs::list_status`] for directories with very large numbers of
+    /// entries, where materializing a `Vec` up front is wasteful.
+    pub fn read_dir(&self, path: &str) -> Result<Readdir, HdfsErr> {
+        let mut num_entries: c_int = 0;
+        let infos = unsafe { hdfsListDirectory(self.raw, to_raw!(path), &mut num_entries) };
+
+        if infos.is_null() && num_entries != 0 {
+            return Err(HdfsErr::Generic(format!("Couldn't list {}", path)));
+        }
+
+        Ok(Readdir {
+            infos,
+            num_entries,
+            index: 0,
+        })
+    }
+
+    /// Lists the entries directly under `path`.
+    pub fn list_status(&self, path: &str) -> Result<Vec<FileStatus>, HdfsErr> {
+        let mut num_entries: c_int = 0;
+        let infos = unsafe { hdfsListDirectory(self.raw, to_raw!(path), &mut num_entries) };
+
+        if infos.is_null() {
+            return if num_entries == 0 {
+                Ok(Vec::new())
+            } else {
+                Err(HdfsErr::Generic(format!("Couldn't list {}", path)))
+            };
+        }
+
+        let entries = unsafe { slice::from_raw_parts(infos, num_entries as usize) };
+        let statuses = entries.iter().map(FileStatus::from_raw).collect();
+
+        unsafe { hdfsFreeFileInfo(infos, num_entries) };
+        Ok(statuses)
+    }
+}
+
+/// Builder for how a file is opened, mirroring `hdfsOpenFile`'s flag and
+/// sizing parameters.
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    buffer_size: Option<i32>,
+    replication: Option<i16>,
+    block_size: Option<i64>,
+}
+
+impl OpenOptions {
+    pub fn new() -> OpenOptions {
+        OpenOptions::default()
+    }
+
+    /// Opens the file for reading (`O_RDONLY`). This is the default.
+    pub fn read(mut self, read: bool) -> Self {
+        self.read = read;
+        self
+    }
+
+    /// Opens the file for writing (`O_WRONLY`).
+    pub fn write(mut self, write: bool) -> Self {
+        self.write = write;
+        self
+    }
+
+    /// Opens the file for appending (`O_APPEND`), implies `write`.
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    /// Overrides the client-side buffer size, in bytes.
+    pub fn buffer_size(mut self, buffer_size: i32) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// Overrides the replication factor for a newly created file.
+    pub fn replication(mut self, replication: i16) -> Self {
+        self.replication = Some(replication);
+        self
+    }
+
+    /// Overrides the block size, in bytes, for a newly created file.
+    pub fn block_size(mut self, block_size: i64) -> Self {
+        self.block_size = Some(block_size);
+        self
+    }
+
+    fn flags(&self) -> c_int {
+        let mut flags = if self.write || self.append {
+            O_WRONLY
+        } else {
+            O_RDONLY
+        };
+        if self.append {
+            flags |= O_APPEND;
+        }
+        flags
+    }
+
+    /// Opens `path` on `fs` with these options.
+    pub fn open(&self, fs: &HdfsFs, path: &str) -> Result<HdfsFile, HdfsErr> {
+        fs.open_with(path, self)
+    }
+}
+
+/// An open file handle for reading or writing.
+pub struct HdfsFile {
+    fs: hdfsFS,
+    raw: hdfsFile,
+}
+
+unsafe impl Send for HdfsFile {}
+
+impl HdfsFile {
+    /// Reads up to `buf.len()` bytes starting at the absolute `offset`,
+    /// without moving the file's stream cursor. Returns the number of bytes
+    /// actually read, which is `0` at EOF and may be less than `buf.len()`
+    /// for a short read that isn't at EOF.
+    pub fn pread(&self, offset: i64, buf: &mut [u8]) -> Result<i32, HdfsErr> {
+        let ret = unsafe {
+            hdfsPread(
+                self.fs,
+                self.raw,
+                offset,
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len() as i32,
+            )
+        };
+
+        if ret < 0 {
+            Err(HdfsErr::Generic(format!(
+                "Couldn't pread {} bytes at offset {}",
+                buf.len(),
+                offset
+            )))
+        } else {
+            Ok(ret)
+        }
+    }
+
+    /// Closes the file, flushing any buffered writes.
+    pub fn close(&self) -> Result<(), HdfsErr> {
+        let ret = unsafe { hdfsCloseFile(self.fs, self.raw) };
+
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(HdfsErr::Generic("Couldn't close file".to_string()))
+        }
+    }
+}
+
+/// A streaming iterator over the entries of a directory, obtained from
+/// [`HdfsFs::read_dir`]. Each call to `next` converts one native
+/// `hdfsFileInfo` entry into an owned [`FileStatus`] without materializing
+/// the whole directory into a `Vec` up front; the native array itself is
+/// freed in one `hdfsFreeFileInfo` call once the iterator is dropped, since
+/// that API frees its base pointer and cannot be called on a sub-slice of
+/// its own allocation.
+pub struct Readdir {
+    infos: *mut hdfsFileInfo,
+    num_entries: c_int,
+    index: c_int,
+}
+
+unsafe impl Send for Readdir {}
+
+impl Iterator for Readdir {
+    type Item = Result<FileStatus, HdfsErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.num_entries {
+            return None;
+        }
+
+        let entry = unsafe { &*self.infos.offset(self.index as isize) };
+        let status = FileStatus::from_raw(entry);
+        self.index += 1;
+
+        Some(Ok(status))
+    }
+}
+
+impl Drop for Readdir {
+    fn drop(&mut self) {
+        if !self.infos.is_null() {
+            unsafe {
+                hdfsFreeFileInfo(self.infos, self.num_entries);
+            }
+        }
+    }
+}
+
+/// Metadata describing a single file or directory entry.
+#[derive(Debug, Clone)]
+pub struct FileStatus {
+    name: String,
+    is_directory: bool,
+    size: i64,
+    replication: i16,
+    block_size: i64,
+    owner: String,
+    group: String,
+    permissions: i16,
+    last_modified: i64,
+    last_access: i64,
+}
+
+impl FileStatus {
+    fn from_raw(info: &hdfsFileInfo) -> FileStatus {
+        FileStatus {
+            name: HdfsUtil::chars_to_string(info.mName),
+            is_directory: info.mKind == b'D' as i32,
+            size: info.mSize,
+            replication: info.mReplication,
+            block_size: info.mBlockSize,
+            owner: HdfsUtil::chars_to_string(info.mOwner),
+            group: HdfsUtil::chars_to_string(info.mGroup),
+            permissions: info.mPermissions,
+            last_modified: info.mLastMod as i64,
+            last_access: info.mLastAccess as i64,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_file(&self) -> bool {
+        !self.is_directory
+    }
+
+    pub fn is_directory(&self) -> bool {
+        self.is_directory
+    }
+
+    pub fn len(&self) -> i64 {
+        self.size
+    }
+
+    pub fn replication(&self) -> i16 {
+        self.replication
+    }
+
+    pub fn block_size(&self) -> i64 {
+        self.block_size
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    pub fn permissions(&self) -> i16 {
+        self.permissions
+    }
+
+    pub fn last_modified(&self) -> i64 {
+        self.last_modified
+    }
+
+    pub fn last_access(&self) -> i64 {
+        self.last_access
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::HdfsRegistry;
+
+    #[test]
+    fn test_chmod_chown_set_replication_and_set_times() -> Result<(), HdfsErr> {
+        let port = 9000;
+        let registry = HdfsRegistry::new();
+        let fs = registry.get(&format!("hdfs://localhost:{}/users/test", port))?;
+
+        let path = "/dfs_attrs_test_file";
+        if fs.exist(path) {
+            fs.delete(path, true)?;
+        }
+        fs.create(path)?.close()?;
+
+        fs.chmod(path, 0o600)?;
+        let status = fs.get_file_status(path)?;
+        assert_eq!(status.permissions(), 0o600);
+
+        fs.chown(path, Some("nobody"), Some("nobody"))?;
+        let status = fs.get_file_status(path)?;
+        assert_eq!(status.owner(), "nobody");
+        assert_eq!(status.group(), "nobody");
+
+        fs.set_replication(path, 1)?;
+        let status = fs.get_file_status(path)?;
+        assert_eq!(status.replication(), 1);
+
+        fs.set_times(path, 1_000_000, 1_000_000)?;
+        let status = fs.get_file_status(path)?;
+        assert_eq!(status.last_modified(), 1_000_000);
+        assert_eq!(status.last_access(), 1_000_000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_dir_streams_all_entries() -> Result<(), HdfsErr> {
+        let port = 9000;
+        let registry = HdfsRegistry::new();
+        let fs = registry.get(&format!("hdfs://localhost:{}/users/test", port))?;
+
+        let dir = "/read_dir_test";
+        if fs.exist(dir) {
+            fs.delete(dir, true)?;
+        }
+        fs.mkdir(dir)?;
+
+        let entry_count = 3;
+        let mut expected_names = Vec::new();
+        for x in 0..entry_count {
+            let filename = format!("{}/{}", dir, x);
+            expected_names.push(filename.clone());
+            fs.create(&filename)?.close()?;
+        }
+
+        let mut names: Vec<String> = fs
+            .read_dir(dir)?
+            .map(|entry| entry.map(|status| status.name().to_string()))
+            .collect::<Result<_, HdfsErr>>()?;
+        names.sort();
+
+        assert_eq!(expected_names, names);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_options_flags_default_is_read_only() {
+        assert_eq!(OpenOptions::new().flags(), O_RDONLY);
+    }
+
+    #[test]
+    fn test_open_options_flags_write() {
+        assert_eq!(OpenOptions::new().write(true).flags(), O_WRONLY);
+    }
+
+    #[test]
+    fn test_open_options_flags_append_implies_write() {
+        assert_eq!(OpenOptions::new().append(true).flags(), O_WRONLY | O_APPEND);
+    }
+
+    #[test]
+    fn test_open_options_flags_append_wins_over_read() {
+        // `append` forces O_WRONLY regardless of whether `read` was also set.
+        assert_eq!(
+            OpenOptions::new().append(true).read(true).flags(),
+            O_WRONLY | O_APPEND
+        );
+    }
+}
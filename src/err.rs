@@ -0,0 +1,46 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Error types returned by this crate.
+
+use std::fmt;
+
+/// Errors returned while interacting with libhdfs3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HdfsErr {
+    /// the given path could not be parsed into a NameNode endpoint
+    InvalidUrl(String),
+    /// failed to connect to the given NameNode
+    CannotConnectToNameNode(String),
+    /// any other failure (a libhdfs3 call, or reading/parsing a config file),
+    /// described by the message
+    Generic(String),
+}
+
+impl fmt::Display for HdfsErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HdfsErr::InvalidUrl(url) => write!(f, "invalid HDFS url: {}", url),
+            HdfsErr::CannotConnectToNameNode(nn) => {
+                write!(f, "cannot connect to NameNode ({})", nn)
+            }
+            HdfsErr::Generic(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HdfsErr {}
@@ -17,6 +17,8 @@
 
 //! A rust wrapper over libhdfs3
 
+/// TOML configuration-file support for building connections
+pub mod config;
 /// Rust APIs wrapping libhdfs3 API, providing better semantic and abstraction
 pub mod dfs;
 pub mod err;
@@ -24,12 +26,15 @@ pub mod err;
 pub mod raw;
 pub mod util;
 
+pub use crate::config::{HdfsNativeConfig, write_sample_config};
 pub use crate::dfs::*;
 pub use crate::err::HdfsErr;
 pub use crate::util::HdfsUtil;
 
 use crate::raw::{
-    hdfsBuilderConnect, hdfsBuilderSetNameNode, hdfsBuilderSetNameNodePort, hdfsFS, hdfsNewBuilder,
+    hdfsBuilderConfSetStr, hdfsBuilderConnect, hdfsBuilderSetNameNode, hdfsBuilderSetNameNodePort,
+    hdfsBuilderSetKerbTicketCachePath, hdfsBuilderSetUserName, hdfsCopy, hdfsDisconnect, hdfsFS,
+    hdfsMove, hdfsNewBuilder,
 };
 use log::info;
 use std::collections::HashMap;
@@ -41,7 +46,23 @@ static LOCAL_FS_SCHEME: &str = "file";
 /// HdfsRegistry which stores seen HdfsFs instances.
 #[derive(Debug)]
 pub struct HdfsRegistry {
-    all_fs: Arc<Mutex<HashMap<String, HdfsFs>>>,
+    all_fs: Arc<Mutex<HashMap<ConnectionProperties, CachedConnection>>>,
+    config: Option<HdfsNativeConfig>,
+}
+
+/// A cached `HdfsFs`, plus how many logical handles (outstanding `get`/
+/// `get_with` callers) currently reference it.
+///
+/// libhdfs3 may hand back the *same* underlying filesystem object for
+/// multiple `hdfsBuilderConnect` calls, so disconnecting as soon as one
+/// caller is done would poison other live clients of that connection with
+/// "Filesystem closed" errors. Reference counting this per
+/// [`ConnectionProperties`] key ensures `hdfsDisconnect` only runs once the
+/// last referencing caller has released it via [`HdfsRegistry::disconnect`].
+#[derive(Debug)]
+pub struct CachedConnection {
+    fs: HdfsFs,
+    ref_count: usize,
 }
 
 impl Default for HdfsRegistry {
@@ -50,21 +71,75 @@ impl Default for HdfsRegistry {
     }
 }
 
-struct HostPort {
-    host: String,
-    port: u16,
+impl Drop for HdfsRegistry {
+    /// Disconnects only cached connections with `ref_count == 0`; entries
+    /// with outstanding logical references are left connected. Since
+    /// [`HdfsRegistry::disconnect_with`] already disconnects and removes an
+    /// entry once its count reaches 0, every entry still cached here has
+    /// outstanding references, so in practice this disconnects nothing. It
+    /// also does nothing while other registries share the map (see
+    /// [`HdfsRegistry::new_from`]).
+    ///
+    /// Cloning an `HdfsFs` does not touch this registry or its ref counts, so
+    /// this still cannot see clones a caller is holding onto without having
+    /// released them first: callers must call `disconnect` for every
+    /// `get`/`get_with` before letting their `HdfsFs` clones and the
+    /// registry itself go out of scope.
+    fn drop(&mut self) {
+        if Arc::strong_count(&self.all_fs) > 1 {
+            return;
+        }
+
+        let mut map = self.all_fs.lock().unwrap();
+        map.retain(|props, conn| {
+            if conn.ref_count > 0 {
+                return true;
+            }
+
+            if unsafe { hdfsDisconnect(conn.fs.raw_handle()) } != 0 {
+                log::warn!("Couldn't disconnect from NameNode ({})", props.to_string());
+            }
+            false
+        });
+    }
 }
 
-enum NNScheme {
-    Local,
-    Remote(HostPort),
+/// Identifies a single logical connection to a NameNode (or the local
+/// filesystem), including the identity it should authenticate as.
+///
+/// Two `ConnectionProperties` that differ only in `namenode_user` or
+/// `kerberos_ticket_cache_path` are considered distinct, so callers needing
+/// different identities against the same NameNode get their own cached
+/// `HdfsFs` rather than colliding on a shared one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConnectionProperties {
+    pub namenode_host: Option<String>,
+    pub namenode_port: Option<u16>,
+    pub namenode_user: Option<String>,
+    pub kerberos_ticket_cache_path: Option<String>,
 }
 
-impl ToString for NNScheme {
+impl ConnectionProperties {
+    /// Connection properties for the local `file://` filesystem.
+    pub fn local() -> ConnectionProperties {
+        ConnectionProperties {
+            namenode_host: None,
+            namenode_port: None,
+            namenode_user: None,
+            kerberos_ticket_cache_path: None,
+        }
+    }
+
+    fn is_local(&self) -> bool {
+        self.namenode_host.is_none()
+    }
+}
+
+impl ToString for ConnectionProperties {
     fn to_string(&self) -> String {
-        match self {
-            NNScheme::Local => "file:///".to_string(),
-            NNScheme::Remote(hp) => format!("{}:{}", hp.host, hp.port),
+        match (&self.namenode_host, self.namenode_port) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            _ => "file:///".to_string(),
         }
     }
 }
@@ -73,24 +148,62 @@ impl HdfsRegistry {
     pub fn new() -> HdfsRegistry {
         HdfsRegistry {
             all_fs: Arc::new(Mutex::new(HashMap::new())),
+            config: None,
+        }
+    }
+
+    pub fn new_from(
+        fs: Arc<Mutex<HashMap<ConnectionProperties, CachedConnection>>>,
+    ) -> HdfsRegistry {
+        HdfsRegistry {
+            all_fs: fs,
+            config: None,
         }
     }
 
-    pub fn new_from(fs: Arc<Mutex<HashMap<String, HdfsFs>>>) -> HdfsRegistry {
-        HdfsRegistry { all_fs: fs }
+    /// Builds a registry whose defaults come from a TOML config file, found
+    /// via [`HdfsNativeConfig::locate`]. Falls back to a registry with no
+    /// defaults (identical to [`HdfsRegistry::new`]) if no config file is
+    /// found anywhere in the search path.
+    pub fn from_config() -> Result<HdfsRegistry, HdfsErr> {
+        Ok(HdfsRegistry {
+            all_fs: Arc::new(Mutex::new(HashMap::new())),
+            config: HdfsNativeConfig::load()?,
+        })
     }
 
-    fn get_namenode(&self, path: &str) -> Result<NNScheme, HdfsErr> {
+    fn get_namenode(&self, path: &str) -> Result<ConnectionProperties, HdfsErr> {
         match Url::parse(path) {
             Ok(url) => {
                 if url.scheme() == LOCAL_FS_SCHEME {
-                    Ok(NNScheme::Local)
+                    Ok(ConnectionProperties::local())
                 } else if url.host().is_some() && url.port().is_some() {
-                    Ok(NNScheme::Remote(HostPort {
-                        host: format!("{}://{}", &url.scheme(), url.host().unwrap()),
-                        port: url.port().unwrap(),
-                    }))
+                    let user = if url.username().is_empty() {
+                        None
+                    } else {
+                        Some(url.username().to_string())
+                    };
+
+                    Ok(ConnectionProperties {
+                        namenode_host: Some(format!("{}://{}", &url.scheme(), url.host().unwrap())),
+                        namenode_port: Some(url.port().unwrap()),
+                        namenode_user: user,
+                        kerberos_ticket_cache_path: None,
+                    })
+                } else if url.host().is_none() {
+                    // Host (and therefore port) fully omitted, e.g.
+                    // "hdfs:///path" — fall back to the configured default
+                    // NameNode, if any.
+                    self.config
+                        .as_ref()
+                        .map(|config| config.connection_properties())
+                        .filter(|props| props.namenode_host.is_some())
+                        .ok_or_else(|| HdfsErr::InvalidUrl(path.to_string()))
                 } else {
+                    // Host given but port omitted, e.g.
+                    // "hdfs://specific-cluster/path" — reject rather than
+                    // silently redirecting to the configured default
+                    // cluster's host/port instead.
                     Err(HdfsErr::InvalidUrl(path.to_string()))
                 }
             }
@@ -98,39 +211,224 @@ impl HdfsRegistry {
         }
     }
 
+    /// Resolves `path` to a NameNode using the URL's own scheme/host/port
+    /// and, if present, a `user@` userinfo component, then connects (or
+    /// reuses a cached connection). A URL that omits the host entirely uses
+    /// the config file's default [`ConnectionProperties`] instead, including
+    /// its user and Kerberos ticket cache path; a URL with a host but no
+    /// port is rejected.
     pub fn get(&self, path: &str) -> Result<HdfsFs, HdfsErr> {
-        let host_port = self.get_namenode(path)?;
+        let props = self.get_namenode(path)?;
+        self.get_with(props)
+    }
 
+    /// Connects (or reuses a cached connection) using an explicit set of
+    /// [`ConnectionProperties`], wiring the Kerberos ticket cache path and/or
+    /// user name into the builder before calling `hdfsBuilderConnect`.
+    pub fn get_with(&self, props: ConnectionProperties) -> Result<HdfsFs, HdfsErr> {
         let mut map = self.all_fs.lock().unwrap();
 
-        let entry: &mut HdfsFs = map.entry(host_port.to_string()).or_insert({
-            let hdfs_fs: *const hdfsFS = unsafe {
-                let hdfs_builder = hdfsNewBuilder();
-                match host_port {
-                    NNScheme::Local => {} //NO-OP
-                    NNScheme::Remote(ref hp) => {
-                        hdfsBuilderSetNameNode(hdfs_builder, to_raw!(&*hp.host));
-                        hdfsBuilderSetNameNodePort(hdfs_builder, hp.port);
+        let entry: &mut CachedConnection = match map.entry(props.clone()) {
+            std::collections::hash_map::Entry::Occupied(e) => {
+                let conn = e.into_mut();
+                conn.ref_count += 1;
+                conn
+            }
+            std::collections::hash_map::Entry::Vacant(e) => {
+                let hdfs_fs: hdfsFS = unsafe {
+                    let hdfs_builder = hdfsNewBuilder();
+                    if !props.is_local() {
+                        hdfsBuilderSetNameNode(
+                            hdfs_builder,
+                            to_raw!(&*props.namenode_host.clone().unwrap()),
+                        );
+                        hdfsBuilderSetNameNodePort(hdfs_builder, props.namenode_port.unwrap());
+                    }
+                    if let Some(ref user) = props.namenode_user {
+                        hdfsBuilderSetUserName(hdfs_builder, to_raw!(&**user));
                     }
+                    if let Some(ref ticket_cache) = props.kerberos_ticket_cache_path {
+                        hdfsBuilderSetKerbTicketCachePath(hdfs_builder, to_raw!(&**ticket_cache));
+                    }
+                    if let Some(ref config) = self.config {
+                        for (key, value) in &config.builder_conf {
+                            let _ = hdfsBuilderConfSetStr(
+                                hdfs_builder,
+                                to_raw!(&**key),
+                                to_raw!(&**value),
+                            );
+                        }
+                    }
+                    info!("Connecting to NameNode ({})", &props.to_string());
+                    hdfsBuilderConnect(hdfs_builder)
+                };
+
+                if hdfs_fs.is_null() {
+                    return Err(HdfsErr::CannotConnectToNameNode(props.to_string()));
                 }
-                info!("Connecting to NameNode ({})", &host_port.to_string());
-                hdfsBuilderConnect(hdfs_builder)
-            };
+                info!("Connected to NameNode ({})", &props.to_string());
+                e.insert(CachedConnection {
+                    fs: HdfsFs::new(props.to_string(), hdfs_fs),
+                    ref_count: 1,
+                })
+            }
+        };
+
+        Ok(entry.fs.clone())
+    }
+
+    /// Releases one logical reference to the connection for `url`, taken out
+    /// by an earlier [`HdfsRegistry::get`]/[`HdfsRegistry::get_with`] call.
+    /// Only when the last reference for that [`ConnectionProperties`] key is
+    /// released does this actually call `hdfsDisconnect` and remove the
+    /// cache entry; other callers still holding a reference under the same
+    /// key are left untouched. Counts are kept per key, not per native
+    /// handle: if libhdfs3 returns one native filesystem for two different
+    /// keys, releasing the last reference of either key disconnects it for
+    /// both.
+    pub fn disconnect(&self, url: &str) -> Result<(), HdfsErr> {
+        let props = self.get_namenode(url)?;
+        self.disconnect_with(props)
+    }
+
+    /// Like [`HdfsRegistry::disconnect`], but takes explicit
+    /// [`ConnectionProperties`] instead of parsing them from a URL.
+    pub fn disconnect_with(&self, props: ConnectionProperties) -> Result<(), HdfsErr> {
+        let mut map = self.all_fs.lock().unwrap();
+
+        if let std::collections::hash_map::Entry::Occupied(mut e) = map.entry(props.clone()) {
+            e.get_mut().ref_count = e.get().ref_count.saturating_sub(1);
 
-            if hdfs_fs.is_null() {
-                return Err(HdfsErr::CannotConnectToNameNode(host_port.to_string()));
+            if e.get().ref_count == 0 {
+                let conn = e.remove();
+                let ret = unsafe { hdfsDisconnect(conn.fs.raw_handle()) };
+
+                if ret != 0 {
+                    return Err(HdfsErr::Generic(format!(
+                        "Couldn't disconnect from NameNode ({})",
+                        props.to_string()
+                    )));
+                }
             }
-            info!("Connected to NameNode ({})", &host_port.to_string());
-            HdfsFs::new(host_port.to_string(), hdfs_fs)
-        });
+        }
+
+        Ok(())
+    }
+
+    /// Resolves `url` to the [`ConnectionProperties`] it was reached through,
+    /// its (cached) `HdfsFs`, and the path component within it.
+    ///
+    /// This takes out a logical reference via [`HdfsRegistry::get_with`];
+    /// callers that only need the connection transiently (like
+    /// [`HdfsRegistry::copy`]/[`HdfsRegistry::move_`]) must release it
+    /// afterwards with [`HdfsRegistry::disconnect_with`].
+    fn resolve(&self, url: &str) -> Result<(ConnectionProperties, HdfsFs, String), HdfsErr> {
+        let props = self.get_namenode(url)?;
+        let fs = self.get_with(props.clone())?;
+
+        let path = Url::parse(url)
+            .map(|u| u.path().to_string())
+            .map_err(|_| HdfsErr::InvalidUrl(url.to_string()))?;
+
+        Ok((props, fs, path))
+    }
+
+    /// Resolves both `src_url` and `dst_url` via [`HdfsRegistry::resolve`].
+    /// If resolving `dst_url` fails, releases the reference already taken
+    /// out for `src_url` before propagating the error, so a failed resolve
+    /// never leaks a logical reference.
+    #[allow(clippy::type_complexity)]
+    fn resolve_pair(
+        &self,
+        src_url: &str,
+        dst_url: &str,
+    ) -> Result<
+        (
+            (ConnectionProperties, HdfsFs, String),
+            (ConnectionProperties, HdfsFs, String),
+        ),
+        HdfsErr,
+    > {
+        let src = self.resolve(src_url)?;
+
+        match self.resolve(dst_url) {
+            Ok(dst) => Ok((src, dst)),
+            Err(e) => {
+                let _ = self.disconnect_with(src.0.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// Copies `src_url` to `dst_url`, which may live on different NameNodes
+    /// (or one may be `file://` and the other remote). Both endpoints are
+    /// resolved independently through [`HdfsRegistry::get`], so if they
+    /// happen to resolve to the same cached `HdfsFs` the copy is simply a
+    /// same-filesystem `hdfsCopy` call.
+    pub fn copy(&self, src_url: &str, dst_url: &str) -> Result<(), HdfsErr> {
+        let ((src_props, src_fs, src_path), (dst_props, dst_fs, dst_path)) =
+            self.resolve_pair(src_url, dst_url)?;
+
+        let ret = unsafe {
+            hdfsCopy(
+                src_fs.raw_handle(),
+                to_raw!(&*src_path),
+                dst_fs.raw_handle(),
+                to_raw!(&*dst_path),
+            )
+        };
 
-        Ok(entry.clone())
+        // Always release both references, regardless of whether `ret` or
+        // either disconnect failed, so a failure on one side never leaks
+        // the other's reference.
+        let src_disconnect = self.disconnect_with(src_props);
+        let dst_disconnect = self.disconnect_with(dst_props);
+
+        if ret != 0 {
+            return Err(HdfsErr::Generic(format!(
+                "Couldn't copy {} to {}",
+                src_url, dst_url
+            )));
+        }
+        src_disconnect?;
+        dst_disconnect?;
+        Ok(())
+    }
+
+    /// Moves `src_url` to `dst_url`, which may live on different NameNodes.
+    /// See [`HdfsRegistry::copy`] for how the two endpoints are resolved.
+    pub fn move_(&self, src_url: &str, dst_url: &str) -> Result<(), HdfsErr> {
+        let ((src_props, src_fs, src_path), (dst_props, dst_fs, dst_path)) =
+            self.resolve_pair(src_url, dst_url)?;
+
+        let ret = unsafe {
+            hdfsMove(
+                src_fs.raw_handle(),
+                to_raw!(&*src_path),
+                dst_fs.raw_handle(),
+                to_raw!(&*dst_path),
+            )
+        };
+
+        let src_disconnect = self.disconnect_with(src_props);
+        let dst_disconnect = self.disconnect_with(dst_props);
+
+        if ret != 0 {
+            return Err(HdfsErr::Generic(format!(
+                "Couldn't move {} to {}",
+                src_url, dst_url
+            )));
+        }
+        src_disconnect?;
+        dst_disconnect?;
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::HdfsRegistry;
+    use super::{ConnectionProperties, HdfsRegistry};
+    use crate::config::{CONFIG_ENV_VAR, ENV_VAR_LOCK};
     use crate::HdfsErr;
     use log::debug;
 
@@ -199,4 +497,124 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_get_namenode_local_file_scheme() {
+        let registry = HdfsRegistry::new();
+
+        assert_eq!(
+            registry.get_namenode("file:///tmp/data").unwrap(),
+            ConnectionProperties::local()
+        );
+    }
+
+    #[test]
+    fn test_get_namenode_remote_with_userinfo() {
+        let registry = HdfsRegistry::new();
+
+        let props = registry
+            .get_namenode("hdfs://alice@localhost:9000/users/test")
+            .unwrap();
+
+        assert_eq!(props.namenode_host.as_deref(), Some("hdfs://localhost"));
+        assert_eq!(props.namenode_port, Some(9000));
+        assert_eq!(props.namenode_user.as_deref(), Some("alice"));
+        assert!(props.kerberos_ticket_cache_path.is_none());
+    }
+
+    #[test]
+    fn test_get_namenode_remote_without_userinfo() {
+        let registry = HdfsRegistry::new();
+
+        let props = registry.get_namenode("hdfs://localhost:9000/path").unwrap();
+
+        assert_eq!(props.namenode_host.as_deref(), Some("hdfs://localhost"));
+        assert!(props.namenode_user.is_none());
+    }
+
+    #[test]
+    fn test_get_namenode_rejects_url_without_port() {
+        let registry = HdfsRegistry::new();
+
+        assert!(registry.get_namenode("hdfs://localhost/path").is_err());
+    }
+
+    #[test]
+    fn test_get_namenode_rejects_unparseable_url() {
+        let registry = HdfsRegistry::new();
+
+        assert!(registry.get_namenode("not a url").is_err());
+    }
+
+    #[test]
+    fn test_get_namenode_rejects_host_without_port_even_with_config_default() {
+        let _guard = ENV_VAR_LOCK.lock().unwrap();
+
+        let path = std::env::temp_dir().join(format!(
+            "hdfs-native-test-{}-namenode-fallback.toml",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            r#"
+namenode_host = "hdfs://configured-default"
+namenode_port = 8020
+"#,
+        )
+        .unwrap();
+        std::env::set_var(CONFIG_ENV_VAR, &path);
+
+        let registry = HdfsRegistry::from_config().unwrap();
+
+        // Host given but port omitted must be rejected, not silently
+        // redirected to the configured default cluster.
+        assert!(registry.get_namenode("hdfs://specific-cluster/path").is_err());
+
+        // A URL that omits the host entirely still falls back to the
+        // configured default.
+        let props = registry.get_namenode("hdfs:path").unwrap();
+        assert_eq!(
+            props.namenode_host.as_deref(),
+            Some("hdfs://configured-default")
+        );
+
+        std::env::remove_var(CONFIG_ENV_VAR);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_copy_and_move_between_paths() -> Result<(), HdfsErr> {
+        let port = 9000;
+        let registry = HdfsRegistry::new();
+
+        let base = format!("hdfs://localhost:{}", port);
+        let fs = registry.get(&format!("{}/users/test", base))?;
+
+        let src_file = "/copy_move_src";
+        let copy_dst_file = "/copy_move_copy_dst";
+        let move_dst_file = "/copy_move_move_dst";
+        for f in [src_file, copy_dst_file, move_dst_file] {
+            if fs.exist(f) {
+                fs.delete(f, true)?;
+            }
+        }
+
+        fs.create(src_file)?.close()?;
+
+        registry.copy(
+            &format!("{}{}", base, src_file),
+            &format!("{}{}", base, copy_dst_file),
+        )?;
+        assert!(fs.exist(src_file));
+        assert!(fs.exist(copy_dst_file));
+
+        registry.move_(
+            &format!("{}{}", base, copy_dst_file),
+            &format!("{}{}", base, move_dst_file),
+        )?;
+        assert!(!fs.exist(copy_dst_file));
+        assert!(fs.exist(move_dst_file));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,125 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Raw FFI bindings to libhdfs3.
+//!
+//! These are thin `extern "C"` declarations for the native libhdfs3 API.
+//! Prefer the safe wrappers in [`crate::dfs`] over calling into this module
+//! directly.
+
+use libc::{c_char, c_int, c_short, c_void, int32_t, int64_t, time_t};
+
+/// Converts a `&str` into a raw, nul-terminated `*const c_char` for passing
+/// across the FFI boundary.
+#[macro_export]
+macro_rules! to_raw {
+    ($s:expr) => {
+        ::std::ffi::CString::new($s).unwrap().as_ptr()
+    };
+}
+
+#[repr(C)]
+pub struct hdfsBuilder {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct hdfsFS_internal {
+    _private: [u8; 0],
+}
+#[allow(non_camel_case_types)]
+pub type hdfsFS = *mut hdfsFS_internal;
+
+#[repr(C)]
+pub struct hdfsFile_internal {
+    _private: [u8; 0],
+}
+#[allow(non_camel_case_types)]
+pub type hdfsFile = *mut hdfsFile_internal;
+
+/// Mirrors libhdfs3's `hdfsFileInfo` struct.
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct hdfsFileInfo {
+    pub mKind: c_int,
+    pub mName: *mut c_char,
+    pub mLastMod: time_t,
+    pub mSize: int64_t,
+    pub mReplication: c_short,
+    pub mBlockSize: int64_t,
+    pub mOwner: *mut c_char,
+    pub mGroup: *mut c_char,
+    pub mPermissions: c_short,
+    pub mLastAccess: time_t,
+}
+
+extern "C" {
+    pub fn hdfsNewBuilder() -> *mut hdfsBuilder;
+    pub fn hdfsFreeBuilder(bld: *mut hdfsBuilder);
+    pub fn hdfsBuilderSetNameNode(bld: *mut hdfsBuilder, namenode: *const c_char);
+    pub fn hdfsBuilderSetNameNodePort(bld: *mut hdfsBuilder, port: u16);
+    pub fn hdfsBuilderSetUserName(bld: *mut hdfsBuilder, userName: *const c_char);
+    pub fn hdfsBuilderSetKerbTicketCachePath(bld: *mut hdfsBuilder, ticketCachePath: *const c_char);
+    pub fn hdfsBuilderConfSetStr(bld: *mut hdfsBuilder, key: *const c_char, val: *const c_char) -> c_int;
+    pub fn hdfsBuilderConnect(bld: *mut hdfsBuilder) -> hdfsFS;
+    pub fn hdfsDisconnect(fs: hdfsFS) -> c_int;
+
+    pub fn hdfsOpenFile(
+        fs: hdfsFS,
+        path: *const c_char,
+        flags: c_int,
+        bufferSize: c_int,
+        replication: c_short,
+        blocksize: int64_t,
+    ) -> hdfsFile;
+    pub fn hdfsCloseFile(fs: hdfsFS, file: hdfsFile) -> c_int;
+    pub fn hdfsExists(fs: hdfsFS, path: *const c_char) -> c_int;
+    pub fn hdfsSeek(fs: hdfsFS, file: hdfsFile, desiredPos: int64_t) -> c_int;
+    pub fn hdfsTell(fs: hdfsFS, file: hdfsFile) -> int64_t;
+    pub fn hdfsRead(fs: hdfsFS, file: hdfsFile, buffer: *mut c_void, length: int32_t) -> int32_t;
+    pub fn hdfsPread(
+        fs: hdfsFS,
+        file: hdfsFile,
+        position: int64_t,
+        buffer: *mut c_void,
+        length: int32_t,
+    ) -> int32_t;
+    pub fn hdfsWrite(fs: hdfsFS, file: hdfsFile, buffer: *const c_void, length: int32_t)
+        -> int32_t;
+    pub fn hdfsFlush(fs: hdfsFS, file: hdfsFile) -> c_int;
+    pub fn hdfsCopy(srcFS: hdfsFS, src: *const c_char, dstFS: hdfsFS, dst: *const c_char) -> c_int;
+    pub fn hdfsMove(srcFS: hdfsFS, src: *const c_char, dstFS: hdfsFS, dst: *const c_char) -> c_int;
+    pub fn hdfsDelete(fs: hdfsFS, path: *const c_char, recursive: c_int) -> c_int;
+    pub fn hdfsRename(fs: hdfsFS, oldPath: *const c_char, newPath: *const c_char) -> c_int;
+    pub fn hdfsCreateDirectory(fs: hdfsFS, path: *const c_char) -> c_int;
+    pub fn hdfsListDirectory(
+        fs: hdfsFS,
+        path: *const c_char,
+        numEntries: *mut c_int,
+    ) -> *mut hdfsFileInfo;
+    pub fn hdfsGetPathInfo(fs: hdfsFS, path: *const c_char) -> *mut hdfsFileInfo;
+    pub fn hdfsFreeFileInfo(infos: *mut hdfsFileInfo, numEntries: c_int);
+    pub fn hdfsChmod(fs: hdfsFS, path: *const c_char, mode: c_short) -> c_int;
+    pub fn hdfsChown(
+        fs: hdfsFS,
+        path: *const c_char,
+        owner: *const c_char,
+        group: *const c_char,
+    ) -> c_int;
+    pub fn hdfsSetReplication(fs: hdfsFS, path: *const c_char, replication: c_short) -> c_int;
+    pub fn hdfsUtime(fs: hdfsFS, path: *const c_char, mtime: time_t, atime: time_t) -> c_int;
+}
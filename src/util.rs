@@ -0,0 +1,37 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Small helpers shared across the crate.
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+/// Miscellaneous helpers for working with native HDFS values.
+pub struct HdfsUtil;
+
+impl HdfsUtil {
+    /// Copies a native, nul-terminated C string into an owned `String`.
+    ///
+    /// Returns an empty string if `ptr` is null.
+    pub fn chars_to_string(ptr: *const c_char) -> String {
+        if ptr.is_null() {
+            return String::new();
+        }
+
+        unsafe { CStr::from_ptr(ptr).to_string_lossy().into_owned() }
+    }
+}